@@ -0,0 +1,122 @@
+//! A frame allocator that bumps forward through the BIOS-reported memory areas.
+
+use multiboot2::{MemoryArea, MemoryAreaIter};
+
+use super::paging::{Frame, FrameAllocator};
+
+/// Capacity of the freed-frame stack below. Fixed-size (the same trick
+/// `TinyAllocator` uses in `paging::temporary_page`) rather than a `Vec`, since
+/// `dealloc_frame` gets called before the kernel heap exists: `remap_kernel`
+/// unmaps the old P4's guard page, which frees a frame, well before
+/// `heap::init` runs. A `Vec`'s first growth would allocate against a
+/// zeroed-out global allocator and return null, aborting boot.
+const FREE_FRAMES_CAPACITY: usize = 64;
+
+pub struct AreaFrameAllocator {
+    next_free_frame: Frame,
+    current_area: Option<&'static MemoryArea>,
+    areas: MemoryAreaIter,
+    kernel_start: Frame,
+    kernel_end: Frame,
+    multiboot_start: Frame,
+    multiboot_end: Frame,
+    /// Indices of frames handed back through `dealloc_frame`, most recently freed
+    /// on top. Checked before falling back to the area-bump logic, so freed
+    /// frames actually get reused. See `FREE_FRAMES_CAPACITY` for why this is a
+    /// fixed-size stack instead of a `Vec`.
+    free_frames: [Option<usize>; FREE_FRAMES_CAPACITY],
+    free_frames_len: usize,
+}
+
+impl AreaFrameAllocator {
+    pub fn new(
+        kernel_start: usize,
+        kernel_end: usize,
+        multiboot_start: usize,
+        multiboot_end: usize,
+        memory_areas: MemoryAreaIter,
+    ) -> AreaFrameAllocator {
+        let mut allocator = AreaFrameAllocator {
+            next_free_frame: Frame::containing_address(0),
+            current_area: None,
+            areas: memory_areas,
+            kernel_start: Frame::containing_address(kernel_start),
+            kernel_end: Frame::containing_address(kernel_end),
+            multiboot_start: Frame::containing_address(multiboot_start),
+            multiboot_end: Frame::containing_address(multiboot_end),
+            free_frames: [None; FREE_FRAMES_CAPACITY],
+            free_frames_len: 0,
+        };
+        allocator.choose_next_area();
+        allocator
+    }
+
+    fn choose_next_area(&mut self) {
+        self.current_area = self
+            .areas
+            .clone()
+            .filter(|area| {
+                let address = area.base_addr + area.length - 1;
+                Frame::containing_address(address as usize) >= self.next_free_frame
+            })
+            .min_by_key(|area| area.base_addr);
+
+        if let Some(area) = self.current_area {
+            let start_frame = Frame::containing_address(area.base_addr as usize);
+            if self.next_free_frame < start_frame {
+                self.next_free_frame = start_frame;
+            }
+        }
+    }
+
+    /// The "cold" source: bumps forward through the reported memory areas,
+    /// skipping the kernel and multiboot regions.
+    fn alloc_frame_bump(&mut self) -> Option<Frame> {
+        if let Some(area) = self.current_area {
+            let frame = Frame { number: self.next_free_frame.number };
+
+            let current_area_last_frame = {
+                let address = area.base_addr + area.length - 1;
+                Frame::containing_address(address as usize)
+            };
+
+            if frame > current_area_last_frame {
+                self.choose_next_area();
+            } else if frame >= self.kernel_start && frame <= self.kernel_end {
+                self.next_free_frame = Frame { number: self.kernel_end.number + 1 };
+            } else if frame >= self.multiboot_start && frame <= self.multiboot_end {
+                self.next_free_frame = Frame { number: self.multiboot_end.number + 1 };
+            } else {
+                self.next_free_frame = frame.next_frame();
+                return Some(frame);
+            }
+            self.alloc_frame_bump()
+        } else {
+            None
+        }
+    }
+}
+
+impl FrameAllocator for AreaFrameAllocator {
+    /// Reclaimed frames (see `dealloc_frame`) are handed out before falling back
+    /// to the area-bump allocator, so freeing and remapping repeatedly doesn't
+    /// exhaust physical memory.
+    fn alloc_frame(&mut self) -> Option<Frame> {
+        if self.free_frames_len > 0 {
+            self.free_frames_len -= 1;
+            let number = self.free_frames[self.free_frames_len].take().unwrap();
+            return Some(Frame { number });
+        }
+        self.alloc_frame_bump()
+    }
+
+    /// Frees past `FREE_FRAMES_CAPACITY` are dropped (the frame leaks) rather than
+    /// growing the stack, since growing would need the heap this allocator is
+    /// meant to work without.
+    fn dealloc_frame(&mut self, frame: Frame) {
+        if self.free_frames_len < FREE_FRAMES_CAPACITY {
+            self.free_frames[self.free_frames_len] = Some(frame.number);
+            self.free_frames_len += 1;
+        }
+    }
+}
@@ -0,0 +1,95 @@
+//! The kernel heap: a fixed virtual range backed by real frames, with a
+//! `#[global_allocator]` installed on top of it so `alloc` (`Box`, `Vec`, `String`,
+//! ...) works everywhere else in the kernel.
+
+use spin::Mutex;
+
+use super::paging::{ActivePageTable, EntryFlags, FrameAllocator, Page};
+
+/// Start of the kernel heap's virtual range. Chosen arbitrarily, far away from the
+/// identity-mapped kernel/multiboot/VGA regions so it can't collide with them.
+pub const HEAP_START: usize = 0x_4444_4444_0000;
+pub const HEAP_SIZE: usize = 1024 * 1024; // 1 MiB
+
+#[global_allocator]
+static ALLOCATOR: LockedHeap = LockedHeap::empty();
+
+/// Maps the kernel heap range into the active table and hands its bounds to the
+/// global allocator. Must run after the table switch in `memory::init`, and before
+/// anything in the kernel reaches for `Box`/`Vec`/`String`.
+pub fn init<A>(active_table: &mut ActivePageTable, allocator: &mut A)
+where
+    A: FrameAllocator,
+{
+    let heap_start_page = Page::containing_address(HEAP_START);
+    let heap_end_page = Page::containing_address(HEAP_START + HEAP_SIZE - 1);
+
+    for page in Page::range_inclusive(heap_start_page, heap_end_page) {
+        // Hundreds of pages get mapped here; invalidate the whole TLB once below
+        // instead of shooting it down page-by-page.
+        active_table.map(page, EntryFlags::WRITABLE, allocator).ignore();
+    }
+    x86_64::instructions::tlb::flush_all();
+
+    unsafe {
+        ALLOCATOR.0.lock().init(HEAP_START, HEAP_SIZE);
+    }
+}
+
+/// A bump allocator over the heap range, guarded by a spinlock so it can sit behind
+/// a single `static` without needing interior synchronization of its own.
+struct LockedHeap(Mutex<BumpAllocator>);
+
+impl LockedHeap {
+    const fn empty() -> LockedHeap {
+        LockedHeap(Mutex::new(BumpAllocator::empty()))
+    }
+}
+
+struct BumpAllocator {
+    next: usize,
+    end: usize,
+}
+
+impl BumpAllocator {
+    const fn empty() -> BumpAllocator {
+        BumpAllocator { next: 0, end: 0 }
+    }
+
+    fn init(&mut self, heap_start: usize, heap_size: usize) {
+        self.next = heap_start;
+        self.end = heap_start + heap_size;
+    }
+
+    fn alloc(&mut self, size: usize, align: usize) -> Option<usize> {
+        let alloc_start = align_up(self.next, align);
+        let alloc_end = alloc_start.checked_add(size)?;
+
+        if alloc_end <= self.end {
+            self.next = alloc_end;
+            Some(alloc_start)
+        } else {
+            None
+        }
+    }
+}
+
+fn align_up(address: usize, align: usize) -> usize {
+    (address + align - 1) & !(align - 1)
+}
+
+use core::alloc::{GlobalAlloc, Layout};
+
+unsafe impl GlobalAlloc for LockedHeap {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        self.0
+            .lock()
+            .alloc(layout.size(), layout.align())
+            .map(|addr| addr as *mut u8)
+            .unwrap_or(core::ptr::null_mut())
+    }
+
+    unsafe fn dealloc(&self, _ptr: *mut u8, _layout: Layout) {
+        // The bump allocator never reclaims space; freeing is a no-op.
+    }
+}
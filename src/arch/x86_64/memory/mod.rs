@@ -3,82 +3,48 @@
 //! Heavly inspired/lovingly ripped off from Phil Oppermann's [os.phil-opp.com](http://os.phil-opp.com/).
 
 mod area_frame_allocator;
+mod heap;
 mod paging;
+mod stack_allocator;
 
 use multiboot2::BootInformation;
 use arch::x86_64;
 
 pub use self::area_frame_allocator::AreaFrameAllocator;
+pub use self::paging::TlsTemplate;
+pub use self::stack_allocator::Stack;
 
 /// The physical size of each frame.
 pub const PAGE_SIZE: usize = 4096;
 
-/// A representation of a frame in physical memory.
-#[derive(PartialEq, Eq, PartialOrd, Ord, Debug)]
-pub struct Frame {
-    index: usize,
+/// Bundles the kernel's paging and allocation state so callers can keep setting
+/// up memory (e.g. handing out stacks to new threads) after `init` returns.
+pub struct MemoryController {
+    active_table: paging::ActivePageTable,
+    frame_allocator: AreaFrameAllocator,
+    stack_allocator: stack_allocator::StackAllocator,
+    tls_template: TlsTemplate,
 }
 
-impl Frame {
-    /// Retrieves the frame containing a particular physical address.
-    fn containing_address(address: usize) -> Frame {
-        Frame {index: address/PAGE_SIZE}
+impl MemoryController {
+    pub fn alloc_stack(&mut self, size_in_pages: usize) -> Option<Stack> {
+        let MemoryController {
+            ref mut active_table,
+            ref mut frame_allocator,
+            ref mut stack_allocator,
+            ..
+        } = *self;
+        stack_allocator.alloc_stack(active_table, frame_allocator, size_in_pages)
     }
 
-    /// Returns the frame after this one.
-    fn next_frame(&self) -> Frame {
-        Frame {index: self.index + 1}
+    /// The kernel's thread-local-storage template, to be copied into every new
+    /// thread's own TLS block.
+    pub fn tls_template(&self) -> &TlsTemplate {
+        &self.tls_template
     }
-
-    fn start_address(&self) -> usize {
-        self.index * PAGE_SIZE
-    }
-
-    /// Returns an iterator of all the frames between `start` and `end` (inclusive).
-    fn range_inclusive(start: Frame, end: Frame) -> FrameIter {
-        FrameIter {
-            start: start,
-            end: end,
-        }
-    }
-
-    /// Clones the Frame; we implement this instead of deriving Clone since deriving clone
-    /// makes `.clone()` public, which would be illogical here (frames should not be cloned by end-users,
-    /// as that could be used to cause, *e.g.*, double-free errors with the `FrameAllocator`).
-    fn clone(&self) -> Frame {
-        Frame { index: self.index }
-    }
-}
-
-struct FrameIter {
-    start: Frame,
-    end: Frame,
 }
 
-impl Iterator for FrameIter {
-    type Item = Frame;
-
-    fn next(&mut self) -> Option<Frame> {
-        if self.start <= self.end {
-            let frame = self.start.clone();
-            self.start.index += 1;
-            Some(frame)
-        } else {
-            None
-        }
-    }
-}
-
-
-/// A trait which can be implemented by any frame allocator, to make the frame allocation system
-/// pluggable.
-pub trait FrameAllocator {
-    fn alloc_frame(&mut self) -> Option<Frame>;
-    fn dealloc_frame(&mut self, frame: Frame);
-}
-
-
-pub fn init(boot_info: &BootInformation) {
+pub fn init(boot_info: &BootInformation) -> MemoryController {
     assert_first_call!("memory::init() can only be called once!");
 
     let memory_map_tag = boot_info.memory_map_tag()
@@ -110,6 +76,26 @@ pub fn init(boot_info: &BootInformation) {
     x86_64::enable_nxe_bit(); // Enable NO_EXECUTE pages
     x86_64::enable_wrprot_bit(); // Disable writing to non-WRITABLE pages
 
-    paging::remap_kernel(&mut frame_allocator, boot_info);
+    let (mut active_table, tls_template) = paging::remap_kernel(&mut frame_allocator, boot_info);
     info!("-- kernel remapped --");
+
+    heap::init(&mut active_table, &mut frame_allocator);
+    info!("-- heap initialized --");
+
+    let stack_allocator = {
+        let stack_alloc_start = heap::HEAP_START + heap::HEAP_SIZE;
+        let stack_alloc_end = stack_alloc_start + 100 * paging::PAGE_SIZE;
+        let stack_alloc_range = paging::Page::range_inclusive(
+            paging::Page::containing_address(stack_alloc_start),
+            paging::Page::containing_address(stack_alloc_end - 1),
+        );
+        stack_allocator::StackAllocator::new(stack_alloc_range)
+    };
+
+    MemoryController {
+        active_table,
+        frame_allocator,
+        stack_allocator,
+        tls_template,
+    }
 }
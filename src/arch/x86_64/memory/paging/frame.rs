@@ -0,0 +1,59 @@
+//! Physical frame bookkeeping for the paging subsystem.
+
+use super::PhysicalAddress;
+
+/// A single frame of physical memory.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Frame {
+    pub(crate) number: usize,
+}
+
+impl Frame {
+    pub const SIZE: usize = 4096;
+
+    /// Retrieves the frame containing a particular physical address.
+    pub fn containing_address(address: PhysicalAddress) -> Frame {
+        Frame { number: address / Self::SIZE }
+    }
+
+    pub fn start_address(&self) -> PhysicalAddress {
+        self.number * Self::SIZE
+    }
+
+    /// Returns the frame right after this one.
+    pub fn next_frame(&self) -> Frame {
+        Frame { number: self.number + 1 }
+    }
+
+    /// Clones the Frame by hand instead of `#[derive(Clone)]`, since deriving would
+    /// make `.clone()` public and let callers duplicate a `Frame` and hand the same
+    /// physical memory to two owners. `pub(crate)` keeps it available to the rest of
+    /// the kernel while still refusing it to external users of this crate.
+    pub(crate) fn clone(&self) -> Frame {
+        Frame { number: self.number }
+    }
+
+    /// Returns an iterator of all the frames between `start` and `end` (inclusive).
+    pub fn range_inclusive(start: Frame, end: Frame) -> FrameIter {
+        FrameIter { start, end }
+    }
+}
+
+pub struct FrameIter {
+    start: Frame,
+    end: Frame,
+}
+
+impl Iterator for FrameIter {
+    type Item = Frame;
+
+    fn next(&mut self) -> Option<Frame> {
+        if self.start <= self.end {
+            let frame = self.start.clone();
+            self.start.number += 1;
+            Some(frame)
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,10 @@
+//! Pluggable frame-allocation strategies usable by the paging subsystem.
+
+use super::Frame;
+
+/// A trait which can be implemented by any frame allocator, to make the frame
+/// allocation system pluggable.
+pub trait FrameAllocator {
+    fn alloc_frame(&mut self) -> Option<Frame>;
+    fn dealloc_frame(&mut self, frame: Frame);
+}
@@ -0,0 +1,154 @@
+//! The mapper: a `Unique` pointer to the (recursively-mapped) active P4 table,
+//! plus the operations that walk and rewrite it.
+
+use core::ptr::Unique;
+
+use x86_64::VirtAddr;
+
+use super::frame::Frame;
+use super::frame_allocators::FrameAllocator;
+use super::page::{Page, PAGE_SIZE};
+use super::table::{EntryFlags, Level4, Table, ENTRY_COUNT, P4};
+use super::{PhysicalAddress, VirtualAddress};
+
+pub struct Mapper {
+    p4: Unique<Table<Level4>>,
+}
+
+impl Mapper {
+    /// Safe only because the P4 pointer always refers to the recursively-mapped
+    /// active table; the caller guarantees exactly one `Mapper` exists at a time.
+    pub unsafe fn new() -> Mapper {
+        Mapper { p4: Unique::new_unchecked(P4) }
+    }
+
+    pub fn p4(&self) -> &Table<Level4> {
+        unsafe { self.p4.as_ref() }
+    }
+
+    pub fn p4_mut(&mut self) -> &mut Table<Level4> {
+        unsafe { self.p4.as_mut() }
+    }
+
+    /// Maps `page` to `frame`, allocating any intermediate P3/P2/P1 tables that
+    /// don't exist yet. Doesn't flush the TLB itself — the returned [`MapperFlush`]
+    /// must be `.flush()`ed or explicitly `.ignore()`d.
+    pub fn map_to<A>(&mut self, page: Page, frame: Frame, flags: EntryFlags, allocator: &mut A) -> MapperFlush
+    where
+        A: FrameAllocator,
+    {
+        let p3 = self.p4_mut().next_table_create(page.p4_index(), allocator);
+        let p2 = p3.next_table_create(page.p3_index(), allocator);
+        let p1 = p2.next_table_create(page.p2_index(), allocator);
+
+        assert!(p1[page.p1_index()].is_unused());
+        p1[page.p1_index()].set(frame, flags | EntryFlags::PRESENT);
+        MapperFlush::new(page)
+    }
+
+    /// Identity-maps `frame`, i.e. maps the page at the same address as the frame.
+    pub fn identity_map<A>(&mut self, frame: Frame, flags: EntryFlags, allocator: &mut A) -> MapperFlush
+    where
+        A: FrameAllocator,
+    {
+        let page = Page::containing_address(frame.start_address());
+        self.map_to(page, frame, flags, allocator)
+    }
+
+    /// Maps `page` to a freshly allocated frame.
+    pub fn map<A>(&mut self, page: Page, flags: EntryFlags, allocator: &mut A) -> MapperFlush
+    where
+        A: FrameAllocator,
+    {
+        let frame = allocator.alloc_frame().expect("out of physical frames");
+        self.map_to(page, frame, flags, allocator)
+    }
+
+    /// Resolves a virtual address to the physical address it's currently mapped to,
+    /// by walking the recursively-mapped tables down to the entry covering it.
+    pub fn translate(&self, virtual_address: VirtualAddress) -> Option<PhysicalAddress> {
+        let offset = virtual_address % PAGE_SIZE;
+        self.translate_page(Page::containing_address(virtual_address))
+            .map(|frame| frame.start_address() + offset)
+    }
+
+    /// Like [`Mapper::translate`], but stops at the containing frame instead of
+    /// resolving all the way down to the byte offset within it.
+    pub fn translate_page(&self, page: Page) -> Option<Frame> {
+        let p3 = self.p4().next_table(page.p4_index());
+
+        let huge_page = || {
+            p3.and_then(|p3| {
+                let p3_entry = &p3[page.p3_index()];
+                // 1 GiB page?
+                if let Some(start_frame) = p3_entry.pointed_frame() {
+                    if p3_entry.flags().contains(EntryFlags::HUGE_PAGE) {
+                        assert!(start_frame.number % (ENTRY_COUNT * ENTRY_COUNT) == 0);
+                        return Some(Frame {
+                            number: start_frame.number + page.p2_index() * ENTRY_COUNT + page.p1_index(),
+                        });
+                    }
+                }
+                if let Some(p2) = p3.next_table(page.p3_index()) {
+                    let p2_entry = &p2[page.p2_index()];
+                    // 2 MiB page?
+                    if let Some(start_frame) = p2_entry.pointed_frame() {
+                        if p2_entry.flags().contains(EntryFlags::HUGE_PAGE) {
+                            assert!(start_frame.number % ENTRY_COUNT == 0);
+                            return Some(Frame { number: start_frame.number + page.p1_index() });
+                        }
+                    }
+                }
+                None
+            })
+        };
+
+        p3.and_then(|p3| p3.next_table(page.p3_index()))
+            .and_then(|p2| p2.next_table(page.p2_index()))
+            .and_then(|p1| p1[page.p1_index()].pointed_frame())
+            .or_else(huge_page)
+    }
+
+    pub fn unmap<A>(&mut self, page: Page, allocator: &mut A)
+    where
+        A: FrameAllocator,
+    {
+        use x86_64::instructions::tlb;
+
+        let p1 = self
+            .p4_mut()
+            .next_table_mut(page.p4_index())
+            .and_then(|p3| p3.next_table_mut(page.p3_index()))
+            .and_then(|p2| p2.next_table_mut(page.p2_index()))
+            .expect("mapping code does not support huge pages");
+        let frame = p1[page.p1_index()].pointed_frame().expect("page not mapped");
+        p1[page.p1_index()].set_unused();
+        tlb::flush_all();
+        allocator.dealloc_frame(frame);
+    }
+}
+
+/// A pending TLB invalidation for one freshly (re)mapped page.
+///
+/// Mapping a page doesn't invalidate its stale TLB entry by itself — callers must
+/// either `.flush()` it immediately, or `.ignore()` it when they're about to map a
+/// batch of pages and will invalidate the whole TLB once at the end instead of
+/// shooting it down after every single page.
+#[must_use = "a mapping isn't visible to the CPU until this is flushed or ignored"]
+pub struct MapperFlush(Page);
+
+impl MapperFlush {
+    fn new(page: Page) -> MapperFlush {
+        MapperFlush(page)
+    }
+
+    /// Invalidates just this page's TLB entry via `invlpg`.
+    pub fn flush(self, _active_table: &mut super::ActivePageTable) {
+        use x86_64::instructions::tlb;
+        tlb::flush(VirtAddr::new(self.0.start_address() as u64));
+    }
+
+    /// Discards the flush; the caller is responsible for flushing the TLB itself,
+    /// e.g. with one `tlb::flush_all()` after mapping a whole batch of pages.
+    pub fn ignore(self) {}
+}
@@ -6,7 +6,7 @@
 #![cfg_attr(feature = "cargo-clippy", allow(unreadable_literal))]
 
 use core::ops::{Deref, DerefMut};
-use multiboot2::BootInformation;
+use multiboot2::{BootInformation, ElfSectionType};
 
 mod frame;
 pub mod frame_allocators;
@@ -18,8 +18,10 @@ mod temporary_page;
 pub use self::frame::Frame;
 pub use self::frame_allocators::FrameAllocator;
 use self::mapper::Mapper;
-pub use self::page::{Page, PageIter};
-use self::table::{EntryFlags, Table};
+pub use self::mapper::MapperFlush;
+pub use self::page::{Page, PageIter, PAGE_SIZE};
+pub use self::table::EntryFlags;
+use self::table::Table;
 use self::temporary_page::TemporaryPage;
 
 /// Helper type aliases used to make function signatures more expressive
@@ -139,8 +141,21 @@ impl InactivePageTable {
     }
 }
 
+/// SHF_TLS: marks an ELF section as belonging to the thread-local template.
+const SHF_TLS: u64 = 1 << 10;
+
+/// The kernel's thread-local-storage template, as found in the ELF sections of the
+/// running image. `file_size` bytes come straight from `.tdata`; the remaining
+/// `mem_size - file_size` bytes are the zero-backed `.tbss` tail. New threads copy
+/// `file_size` bytes from `start_address` and zero the rest to get their own copy.
+pub struct TlsTemplate {
+    pub start_address: usize,
+    pub file_size: usize,
+    pub mem_size: usize,
+}
+
 /// Remap the kernel
-pub fn remap_kernel<A>(allocator: &mut A, boot_info: &BootInformation) -> ActivePageTable
+pub fn remap_kernel<A>(allocator: &mut A, boot_info: &BootInformation) -> (ActivePageTable, TlsTemplate)
 where
     A: FrameAllocator,
 {
@@ -154,6 +169,10 @@ where
         InactivePageTable::new(frame, &mut active_table, &mut scratch_page)
     };
 
+    let mut tls_template = TlsTemplate { start_address: 0, file_size: 0, mem_size: 0 };
+    let mut found_tls_segment = false;
+    let mut tls_end_address = 0;
+
     active_table.with(&mut new_table, &mut scratch_page, |mapper| {
         let elf_sections_tag = boot_info
             .elf_sections_tag()
@@ -176,23 +195,79 @@ where
                 section.size()
             );
 
+            if section.flags().bits() & SHF_TLS != 0 {
+                // SHT_NOBITS ("Uninitialized"): the section occupies no file space
+                // and must be zero-backed (`.tbss`), as opposed to a file-backed
+                // `.tdata` section. The section name isn't a reliable signal here
+                // since a custom linker script can call it anything.
+                let is_zero_backed = section.section_type() == ElfSectionType::Uninitialized;
+                let section_start = section.start_address() as usize;
+                let section_size = section.size() as usize;
+
+                if !found_tls_segment {
+                    found_tls_segment = true;
+                    tls_template = TlsTemplate {
+                        start_address: section_start,
+                        file_size: if is_zero_backed { 0 } else { section_size },
+                        mem_size: section_size,
+                    };
+                } else {
+                    // A real TLS segment is commonly split into a file-backed
+                    // `.tdata` section followed immediately by a zero-backed
+                    // `.tbss` tail; both carry `SHF_TLS`. Merge contiguous TLS
+                    // sections into a single template instead of treating the
+                    // second one as a stray extra segment.
+                    assert!(
+                        section_start == tls_end_address,
+                        "more than one TLS segment found"
+                    );
+                    if !is_zero_backed {
+                        tls_template.file_size += section_size;
+                    }
+                    tls_template.mem_size += section_size;
+                }
+                tls_end_address = section_start + section_size;
+
+                let start_page = Page::containing_address(section.start_address() as usize);
+                let end_page = Page::containing_address(section.end_address() as usize - 1);
+                for page in Page::range_inclusive(start_page, end_page) {
+                    if is_zero_backed {
+                        // `.tbss` has no file content: back it with fresh frames
+                        // instead of identity-mapping the (nonexistent) file data.
+                        mapper.map(page, EntryFlags::WRITABLE, allocator).ignore();
+                        // The frame handed back may hold stale data (e.g. a reused
+                        // frame from `AreaFrameAllocator`'s free-stack), so zero it
+                        // explicitly rather than relying on it being blank.
+                        unsafe {
+                            core::ptr::write_bytes(page.start_address() as *mut u8, 0, PAGE_SIZE);
+                        }
+                    } else {
+                        let frame = Frame::containing_address(page.start_address());
+                        mapper.identity_map(frame, EntryFlags::WRITABLE, allocator).ignore();
+                    }
+                }
+                continue;
+            }
+
             let flags = EntryFlags::from_elf_section_flags(&section);
             let start_frame = Frame::containing_address(section.start_address() as usize);
             let end_frame = Frame::containing_address(section.end_address() as usize - 1);
             for frame in Frame::range_inclusive(start_frame, end_frame) {
-                mapper.identity_map(frame, flags, allocator);
+                // `with` flushes the whole TLB itself once this closure returns, so
+                // there's no point invalidating each page as we go.
+                mapper.identity_map(frame, flags, allocator).ignore();
             }
         }
 
         // -- Identity map the VGA console buffer (it's only one frame long)
         let vga_buffer_frame = Frame::containing_address(0xb8000);
-        mapper.identity_map(vga_buffer_frame, EntryFlags::WRITABLE, allocator);
+        mapper.identity_map(vga_buffer_frame, EntryFlags::WRITABLE, allocator).ignore();
 
         // -- Identity map the multiboot info structure
         let multiboot_start = Frame::containing_address(boot_info.start_address());
         let multiboot_end = Frame::containing_address(boot_info.end_address() - 1);
         for frame in Frame::range_inclusive(multiboot_start, multiboot_end) {
-            mapper.identity_map(frame, EntryFlags::PRESENT | EntryFlags::WRITABLE, allocator);
+            mapper.identity_map(frame, EntryFlags::PRESENT | EntryFlags::WRITABLE, allocator).ignore();
         }
     });
 
@@ -207,5 +282,5 @@ where
         old_p4_page.start_address()
     );
 
-    active_table
+    (active_table, tls_template)
 }
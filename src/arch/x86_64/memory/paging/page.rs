@@ -0,0 +1,74 @@
+//! Virtual pages and iteration helpers; the virtual-memory counterpart to `Frame`.
+
+use super::VirtualAddress;
+
+pub const PAGE_SIZE: usize = 4096;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Page {
+    number: usize,
+}
+
+impl Page {
+    /// Retrieves the page containing a particular virtual address.
+    pub fn containing_address(address: VirtualAddress) -> Page {
+        assert!(
+            address < 0x0000_8000_0000_0000 || address >= 0xffff_8000_0000_0000,
+            "invalid address: 0x{:x}",
+            address
+        );
+        Page { number: address / PAGE_SIZE }
+    }
+
+    /// Builds a page directly from a raw page number, bypassing the canonical-address
+    /// check in [`Page::containing_address`] — used for picking scratch slots that
+    /// will never back real data.
+    pub fn new(number: usize) -> Page {
+        Page { number }
+    }
+
+    pub fn start_address(&self) -> usize {
+        self.number * PAGE_SIZE
+    }
+
+    pub fn p4_index(&self) -> usize {
+        (self.number >> 27) & 0o777
+    }
+
+    pub fn p3_index(&self) -> usize {
+        (self.number >> 18) & 0o777
+    }
+
+    pub fn p2_index(&self) -> usize {
+        (self.number >> 9) & 0o777
+    }
+
+    pub fn p1_index(&self) -> usize {
+        self.number & 0o777
+    }
+
+    /// Returns an iterator of all the pages between `start` and `end` (inclusive).
+    pub fn range_inclusive(start: Page, end: Page) -> PageIter {
+        PageIter { start, end }
+    }
+}
+
+#[derive(Clone)]
+pub struct PageIter {
+    start: Page,
+    end: Page,
+}
+
+impl Iterator for PageIter {
+    type Item = Page;
+
+    fn next(&mut self) -> Option<Page> {
+        if self.start <= self.end {
+            let page = self.start;
+            self.start.number += 1;
+            Some(page)
+        } else {
+            None
+        }
+    }
+}
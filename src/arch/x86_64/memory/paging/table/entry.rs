@@ -0,0 +1,108 @@
+//! A single page-table entry and the flags it can carry.
+
+use core::ops::{BitOr, BitOrAssign};
+
+use multiboot2::ElfSection;
+
+use super::super::frame::Frame;
+
+pub struct Entry(u64);
+
+const ADDRESS_MASK: u64 = 0x000f_ffff_ffff_f000;
+
+impl Entry {
+    pub fn is_unused(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn set_unused(&mut self) {
+        self.0 = 0;
+    }
+
+    pub fn flags(&self) -> EntryFlags {
+        EntryFlags::from_bits_truncate(self.0)
+    }
+
+    pub fn pointed_frame(&self) -> Option<Frame> {
+        if self.flags().contains(EntryFlags::PRESENT) {
+            Some(Frame::containing_address((self.0 & ADDRESS_MASK) as usize))
+        } else {
+            None
+        }
+    }
+
+    pub fn set(&mut self, frame: Frame, flags: EntryFlags) {
+        assert_eq!(frame.start_address() as u64 & !ADDRESS_MASK, 0);
+        self.0 = (frame.start_address() as u64) | flags.bits();
+    }
+}
+
+/// Flags of a page-table entry.
+///
+/// Modelled by hand (rather than pulled from a `bitflags` crate) since it's a single
+/// small `u64` wrapper and `#![feature(const_fn)]` lets `empty()` stay a `const fn`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EntryFlags(u64);
+
+impl EntryFlags {
+    pub const PRESENT: EntryFlags = EntryFlags(1 << 0);
+    pub const WRITABLE: EntryFlags = EntryFlags(1 << 1);
+    pub const USER_ACCESSIBLE: EntryFlags = EntryFlags(1 << 2);
+    pub const WRITE_THROUGH: EntryFlags = EntryFlags(1 << 3);
+    pub const NO_CACHE: EntryFlags = EntryFlags(1 << 4);
+    pub const ACCESSED: EntryFlags = EntryFlags(1 << 5);
+    pub const DIRTY: EntryFlags = EntryFlags(1 << 6);
+    pub const HUGE_PAGE: EntryFlags = EntryFlags(1 << 7);
+    pub const GLOBAL: EntryFlags = EntryFlags(1 << 8);
+    pub const NO_EXECUTE: EntryFlags = EntryFlags(1 << 63);
+
+    pub const fn empty() -> EntryFlags {
+        EntryFlags(0)
+    }
+
+    /// Keeps only the bits of the flags defined above, dropping the frame address
+    /// and the other unused bits.
+    pub fn from_bits_truncate(bits: u64) -> EntryFlags {
+        let known: u64 = ((1 << 9) - 1) | (1 << 63);
+        EntryFlags(bits & known)
+    }
+
+    pub fn bits(&self) -> u64 {
+        self.0
+    }
+
+    pub fn contains(&self, other: EntryFlags) -> bool {
+        (self.0 & other.0) == other.0
+    }
+
+    pub fn from_elf_section_flags(section: &ElfSection) -> EntryFlags {
+        let section_flags = section.flags();
+        let mut flags = EntryFlags::empty();
+
+        if section.is_allocated() {
+            flags |= EntryFlags::PRESENT;
+        }
+        if section_flags.is_writable() {
+            flags |= EntryFlags::WRITABLE;
+        }
+        if !section_flags.is_executable() {
+            flags |= EntryFlags::NO_EXECUTE;
+        }
+
+        flags
+    }
+}
+
+impl BitOr for EntryFlags {
+    type Output = EntryFlags;
+
+    fn bitor(self, rhs: EntryFlags) -> EntryFlags {
+        EntryFlags(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for EntryFlags {
+    fn bitor_assign(&mut self, rhs: EntryFlags) {
+        self.0 |= rhs.0;
+    }
+}
@@ -0,0 +1,81 @@
+//! A single scratch virtual page used to map arbitrary physical frames for the
+//! duration of a table-rewiring operation.
+
+use super::{ActivePageTable, Page, VirtualAddress};
+use super::frame::Frame;
+use super::frame_allocators::FrameAllocator;
+use super::table::{Level1, Table};
+
+pub struct TemporaryPage {
+    page: Page,
+    allocator: TinyAllocator,
+}
+
+impl TemporaryPage {
+    pub fn new<A>(page: Page, allocator: &mut A) -> TemporaryPage
+    where
+        A: FrameAllocator,
+    {
+        TemporaryPage {
+            page: page,
+            allocator: TinyAllocator::new(allocator),
+        }
+    }
+
+    /// Maps the temporary page to the given frame in the active table.
+    pub fn map(&mut self, frame: Frame, active_table: &mut ActivePageTable) -> VirtualAddress {
+        use super::table::EntryFlags;
+
+        active_table
+            .map_to(self.page, frame, EntryFlags::WRITABLE, &mut self.allocator)
+            .flush(active_table);
+        self.page.start_address()
+    }
+
+    /// Maps the temporary page to `frame`, then hands back a handle to it treated as
+    /// a `Level1` table (the layout is level-independent, so this is safe regardless
+    /// of what level the frame actually holds).
+    pub fn map_table_frame(&mut self, frame: Frame, active_table: &mut ActivePageTable) -> &mut Table<Level1> {
+        unsafe { &mut *(self.map(frame, active_table) as *mut Table<Level1>) }
+    }
+
+    pub fn unmap(&mut self, active_table: &mut ActivePageTable) {
+        active_table.unmap(self.page, &mut self.allocator);
+    }
+}
+
+/// A tiny frame allocator holding up to 3 frames, the maximum a single mapping
+/// operation can consume when it has to allocate new P3/P2/P1 tables.
+struct TinyAllocator([Option<Frame>; 3]);
+
+impl TinyAllocator {
+    fn new<A>(allocator: &mut A) -> TinyAllocator
+    where
+        A: FrameAllocator,
+    {
+        let mut f = || allocator.alloc_frame();
+        let frames = [f(), f(), f()];
+        TinyAllocator(frames)
+    }
+}
+
+impl FrameAllocator for TinyAllocator {
+    fn alloc_frame(&mut self) -> Option<Frame> {
+        for frame_option in &mut self.0 {
+            if frame_option.is_some() {
+                return frame_option.take();
+            }
+        }
+        None
+    }
+
+    fn dealloc_frame(&mut self, frame: Frame) {
+        for frame_option in &mut self.0 {
+            if frame_option.is_none() {
+                *frame_option = Some(frame);
+                return;
+            }
+        }
+        panic!("TinyAllocator can only hold 3 frames");
+    }
+}
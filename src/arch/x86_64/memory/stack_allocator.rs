@@ -0,0 +1,77 @@
+//! Carves guard-paged stacks out of a dedicated range of virtual pages.
+
+use super::paging::{ActivePageTable, EntryFlags, FrameAllocator, Page, PageIter, PAGE_SIZE};
+
+pub struct StackAllocator {
+    range: PageIter,
+}
+
+impl StackAllocator {
+    pub fn new(page_range: PageIter) -> StackAllocator {
+        StackAllocator { range: page_range }
+    }
+
+    /// Reserves one page as an unmapped guard page, then maps the next
+    /// `size_in_pages` pages as the stack itself, so a stack overflow faults
+    /// against the guard page instead of silently corrupting whatever comes next.
+    pub fn alloc_stack<A>(
+        &mut self,
+        active_table: &mut ActivePageTable,
+        frame_allocator: &mut A,
+        size_in_pages: usize,
+    ) -> Option<Stack>
+    where
+        A: FrameAllocator,
+    {
+        if size_in_pages == 0 {
+            return None; // a guard page alone isn't a stack
+        }
+
+        let mut range = self.range.clone();
+
+        let guard_page = range.next();
+        let stack_start = range.next();
+        let stack_end = if size_in_pages == 1 {
+            stack_start
+        } else {
+            range.nth(size_in_pages - 2)
+        };
+
+        match (guard_page, stack_start, stack_end) {
+            (Some(_), Some(start), Some(end)) => {
+                self.range = range;
+
+                for page in Page::range_inclusive(start, end) {
+                    active_table.map(page, EntryFlags::WRITABLE, frame_allocator).ignore();
+                }
+                x86_64::instructions::tlb::flush_all();
+
+                let top_of_stack = end.start_address() + PAGE_SIZE;
+                Some(Stack::new(top_of_stack, start.start_address()))
+            }
+            _ => None, // not enough pages left in the range
+        }
+    }
+}
+
+/// A kernel stack with a guard page immediately below `bottom`.
+#[derive(Debug)]
+pub struct Stack {
+    top: usize,
+    bottom: usize,
+}
+
+impl Stack {
+    fn new(top: usize, bottom: usize) -> Stack {
+        assert!(top > bottom);
+        Stack { top, bottom }
+    }
+
+    pub fn top(&self) -> usize {
+        self.top
+    }
+
+    pub fn bottom(&self) -> usize {
+        self.bottom
+    }
+}
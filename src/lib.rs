@@ -2,8 +2,11 @@
 #![feature(unique)]
 #![feature(const_fn)]
 #![feature(lang_items)]
+#![feature(alloc)]
+#![feature(global_allocator)]
 #![no_std]
 
+extern crate alloc;
 extern crate rlibc;
 extern crate spin;
 extern crate volatile;